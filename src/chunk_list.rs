@@ -1,16 +1,37 @@
+use std::cell::Cell;
 use std::cmp;
 use std::mem;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
 
 use {MIN_CAPACITY, INITIAL_SIZE};
 
 
 /// The list of chunks - `Vec<T>`s holding allocated objects - comprising the
 /// arena.
+///
+/// `current`'s `Vec`-level length is kept accurate after every single
+/// `alloc`; `ptr`/`end` are just a cached bump-allocation cursor into
+/// `current`'s spare capacity, re-derived from `current` whenever it's
+/// rebuilt (`reserve`, `alloc_extend`, `alloc_uninitialized`). Keeping the
+/// length eagerly in sync - rather than lazily reconciling it in a `Drop`
+/// impl - means `ChunkList` has no destructor of its own, so dropck treats
+/// it exactly like the `Vec<T>`s it holds: a `T` borrowing from the arena
+/// itself is allowed to dangle relative to the arena, which is what makes
+/// self-referential allocation (`arena.alloc(Node(Some(arena.alloc(..)), ..))`)
+/// compile.
 pub struct ChunkList<T> {
     current: Vec<T>,
-    rest: Vec<Vec<T>>,
+    pub(crate) rest: Vec<Vec<T>>,
+    ptr: Cell<*mut T>,
+    end: Cell<*mut T>,
 }
 
+// `ptr`/`end` are just a cursor into memory that `current`/`rest` already
+// own, so `ChunkList<T>` is `Send` exactly when a `Vec<T>` is.
+unsafe impl<T: Send> Send for ChunkList<T> {}
+
 
 impl<T> ChunkList<T> {
     /// Create a new `ChunkList` of approximately `INITIAL_SIZE` bytes with
@@ -26,9 +47,14 @@ impl<T> ChunkList<T> {
     /// `current` chunk.
     pub fn with_capacity(n: usize) -> ChunkList<T> {
         let n = cmp::max(MIN_CAPACITY, n);
+        let mut current: Vec<T> = Vec::with_capacity(n);
+        let ptr = current.as_mut_ptr();
+        let end = unsafe { ptr.add(current.capacity()) };
         ChunkList {
-            current: Vec::with_capacity(n),
+            current,
             rest: vec![],
+            ptr: Cell::new(ptr),
+            end: Cell::new(end),
         }
     }
 
@@ -46,11 +72,50 @@ impl<T> ChunkList<T> {
             .checked_next_power_of_two()
             .expect("capacity overflow");
         let new_capacity = cmp::max(double_cap, required_cap);
-        let chunk = mem::replace(&mut self.current, Vec::with_capacity(new_capacity));
+
+        let mut new_current = Vec::with_capacity(new_capacity);
+        self.ptr.set(new_current.as_mut_ptr());
+        self.end.set(unsafe { new_current.as_mut_ptr().add(new_capacity) });
+
+        let chunk = mem::replace(&mut self.current, new_current);
         self.rest.push(chunk);
     }
 
 
+    /// Allocate a single `t`, bumping the chunk's cursor.
+    ///
+    /// This is the hot path `alloc` advertises: no iterator, no size hint,
+    /// just a pointer compare and a write. Falls back to `reserve` only when
+    /// `current` is out of room.
+    ///
+    /// Zero-sized `T`s skip the cursor entirely and go through `Vec::push`
+    /// directly: `current`'s capacity is always `usize::MAX` for a ZST, so
+    /// `ptr.add(capacity)` collapses to `ptr` itself (every offset is zero
+    /// bytes), which would make the `ptr == end` check below always true and
+    /// send every allocation through `reserve`, where `capacity.checked_mul(2)`
+    /// overflows and panics.
+    pub fn alloc(&mut self, t: T) -> &mut T {
+        if mem::size_of::<T>() == 0 {
+            let index = self.current.len();
+            self.current.push(t);
+            return &mut self.current[index];
+        }
+
+        let mut ptr = self.ptr.get();
+        if ptr == self.end.get() {
+            self.reserve(1);
+            ptr = self.ptr.get();
+        }
+        unsafe {
+            ptr::write(ptr, t);
+            self.ptr.set(ptr.add(1));
+            let len = self.current.len();
+            self.current.set_len(len + 1);
+            &mut *ptr
+        }
+    }
+
+
     pub fn alloc_extend<I: IntoIterator<Item = T>>(&mut self, iterable: I) -> &mut [T] {
         let mut iter = iterable.into_iter();
 
@@ -82,6 +147,69 @@ impl<T> ChunkList<T> {
             }
         }
 
+        // `current`'s `Vec` bookkeeping now accounts for everything we just
+        // wrote; re-derive the bump cursor from it so `alloc`'s fast path
+        // picks up right where this left off.
+        self.ptr.set(unsafe { self.current.as_mut_ptr().add(self.current.len()) });
+        self.end.set(unsafe { self.current.as_mut_ptr().add(self.current.capacity()) });
+
         &mut self.current[next_item_index..]
     }
+
+    /// Reserve `n` contiguous, uninitialized slots in a single chunk,
+    /// growing `current` (losing whatever spare capacity it had left) if it
+    /// can't hold `n` on its own.
+    ///
+    /// The returned slots are claimed immediately - subsequent allocations
+    /// will not overlap them - so the caller must initialize every element
+    /// of the returned slice (e.g. via `assume_init_slice`) before the next
+    /// access to the arena; an uninitialized slot left behind is read, and
+    /// eventually dropped, as a `T`.
+    pub fn alloc_uninitialized(&mut self, n: usize) -> &mut [MaybeUninit<T>] {
+        if self.current.capacity() - self.current.len() < n {
+            self.reserve(n);
+        }
+
+        let start = self.current.len();
+        unsafe {
+            self.current.set_len(start + n);
+            self.ptr.set(self.current.as_mut_ptr().add(start + n));
+            self.end.set(self.current.as_mut_ptr().add(self.current.capacity()));
+
+            let slots = self.current.as_mut_ptr().add(start) as *mut MaybeUninit<T>;
+            slice::from_raw_parts_mut(slots, n)
+        }
+    }
+
+    /// Consume the chunk list, returning every allocated `T` in allocation
+    /// order as a single `Vec`.
+    pub fn into_vec(self) -> Vec<T> {
+        let ChunkList { current, rest, .. } = self;
+
+        let mut combined = Vec::with_capacity(rest.iter().map(Vec::len).sum::<usize>() +
+                                               current.len());
+        for chunk in rest {
+            combined.extend(chunk);
+        }
+        combined.extend(current);
+        combined
+    }
+
+    /// Iterate mutably over every live element, in allocation order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.rest
+            .iter_mut()
+            .flat_map(|chunk| chunk.iter_mut())
+            .chain(self.current.iter_mut())
+    }
+
+    /// The number of `T`s allocated so far.
+    pub fn len(&self) -> usize {
+        self.rest.iter().map(Vec::len).sum::<usize>() + self.current.len()
+    }
+
+    /// Whether any `T`s have been allocated yet.
+    pub fn is_empty(&self) -> bool {
+        self.current.is_empty() && self.rest.iter().all(Vec::is_empty)
+    }
 }
@@ -0,0 +1,184 @@
+use std::alloc::Layout;
+use std::cell::RefCell;
+use std::cmp;
+use std::mem;
+use std::slice;
+use std::str;
+
+use INITIAL_SIZE;
+
+/// An arena that bump-allocates raw, untyped bytes and hands back references
+/// into them.
+///
+/// Unlike [`unsync::Arena`](../unsync/struct.Arena.html), `DroplessArena` is
+/// not generic over a single `T` - a single arena can back values of many
+/// different types, which makes it a good fit for things like heterogeneous
+/// AST node sets. The tradeoff is right there in the name: a `DroplessArena`
+/// never runs destructors on the values it allocates, so it is only sound to
+/// allocate types for which leaking (rather than running `Drop`) is
+/// acceptable - in particular, any `T: Copy`, since such a `T` cannot have a
+/// non-trivial `Drop` impl.
+///
+/// Chunks are never reallocated once pushed, so references handed out by
+/// `alloc` remain valid for as long as the arena is alive.
+pub struct DroplessArena {
+    chunks: RefCell<ChunkList>,
+}
+
+struct ChunkList {
+    current: Vec<u8>,
+    rest: Vec<Vec<u8>>,
+    start: *mut u8,
+    end: *mut u8,
+}
+
+impl ChunkList {
+    fn with_capacity(n: usize) -> ChunkList {
+        let mut current: Vec<u8> = Vec::with_capacity(cmp::max(1, n));
+        let start = current.as_mut_ptr();
+        let end = unsafe { start.add(current.capacity()) };
+        ChunkList {
+            current,
+            rest: vec![],
+            start,
+            end,
+        }
+    }
+
+    #[inline(never)]
+    #[cold]
+    fn grow(&mut self, needed: usize) {
+        let double_cap = self.current.capacity().checked_mul(2).expect("capacity overflow");
+        let new_capacity = cmp::max(double_cap, needed).next_power_of_two();
+        let mut new_current: Vec<u8> = Vec::with_capacity(new_capacity);
+        self.start = new_current.as_mut_ptr();
+        self.end = unsafe { self.start.add(new_current.capacity()) };
+        let chunk = mem::replace(&mut self.current, new_current);
+        self.rest.push(chunk);
+    }
+
+    /// Bump-allocate `size` bytes aligned to `align`, returning a pointer to
+    /// the start of the allocation.
+    fn alloc_raw(&mut self, size: usize, align: usize) -> *mut u8 {
+        loop {
+            let aligned = align_up(self.start, align);
+            let available = (self.end as usize).saturating_sub(aligned as usize);
+            if size <= available {
+                self.start = unsafe { aligned.add(size) };
+                return aligned;
+            }
+            // Either the current chunk has no room at all, or not enough
+            // once aligned; grow and retry against the fresh chunk.
+            self.grow(size + align);
+        }
+    }
+}
+
+fn align_up(ptr: *mut u8, align: usize) -> *mut u8 {
+    let addr = ptr as usize;
+    // `addr + align - 1` can overflow for a high address; saturate instead,
+    // which makes the result fail `alloc_raw`'s `size <= available` check
+    // and fall through to `grow` rather than silently wrapping to a bogus,
+    // misaligned pointer.
+    let aligned = addr.checked_add(align - 1)
+        .map(|sum| sum & !(align - 1))
+        .unwrap_or(usize::MAX);
+    aligned as *mut u8
+}
+
+impl DroplessArena {
+    /// Create a new `DroplessArena` with a default size of approximately
+    /// 1024 bytes.
+    pub fn new() -> DroplessArena {
+        DroplessArena { chunks: RefCell::new(ChunkList::with_capacity(INITIAL_SIZE)) }
+    }
+
+    /// Create a new `DroplessArena` with enough capacity for at least
+    /// `bytes` bytes without a reallocation.
+    pub fn with_capacity(bytes: usize) -> DroplessArena {
+        DroplessArena { chunks: RefCell::new(ChunkList::with_capacity(bytes)) }
+    }
+
+    /// Allocate a single `T`, handing back a mutable reference into the
+    /// arena.
+    ///
+    /// Calling this with a `T` that has a non-trivial `Drop` impl is
+    /// unsound: the arena never drops its contents, so `T::drop` will never
+    /// run.
+    // Each call claims a fresh, disjoint region of the arena, so handing out
+    // `&mut` from `&self` is sound - same tradeoff `unsync::Arena::alloc` and
+    // `sync::Arena::alloc` already make.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, t: T) -> &mut T {
+        assert!(mem::size_of::<T>() != 0, "DroplessArena cannot allocate zero-sized types");
+        let layout = Layout::new::<T>();
+        unsafe {
+            let ptr = self.chunks.borrow_mut().alloc_raw(layout.size(), layout.align()) as *mut T;
+            ptr.write(t);
+            &mut *ptr
+        }
+    }
+
+    /// Copy `slice` into the arena, handing back a mutable reference to the
+    /// copy.
+    // See `alloc` for why `&mut` from `&self` is sound here too.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        if slice.is_empty() {
+            return &mut [];
+        }
+        let layout = Layout::array::<T>(slice.len()).expect("capacity overflow");
+        unsafe {
+            let ptr = self.chunks.borrow_mut().alloc_raw(layout.size(), layout.align()) as *mut T;
+            ptr.copy_from_nonoverlapping(slice.as_ptr(), slice.len());
+            slice::from_raw_parts_mut(ptr, slice.len())
+        }
+    }
+
+    /// Copy `s` into the arena, handing back a reference to the copy.
+    pub fn alloc_str(&self, s: &str) -> &str {
+        let copy = self.alloc_slice_copy(s.as_bytes());
+        unsafe { str::from_utf8_unchecked(copy) }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn alloc_roundtrips_value() {
+        let arena = DroplessArena::new();
+        let x = arena.alloc(42u32);
+        assert_eq!(*x, 42);
+    }
+
+    #[test]
+    fn alloc_slice_copy_empty_slice() {
+        let arena = DroplessArena::new();
+        let s: &[u32] = arena.alloc_slice_copy(&[]);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn alloc_str_roundtrips() {
+        let arena = DroplessArena::new();
+        let s = arena.alloc_str("hello, arena");
+        assert_eq!(s, "hello, arena");
+    }
+
+    #[test]
+    fn alloc_stays_aligned_across_chunk_boundaries() {
+        // Start with a tiny chunk so interleaving single bytes with aligned
+        // `u32`s forces `alloc_raw` to grow - and retry `align_up` against -
+        // a fresh chunk many times over.
+        let arena = DroplessArena::with_capacity(8);
+        for i in 0..64u32 {
+            arena.alloc(0u8);
+            let x = arena.alloc(i);
+            assert_eq!(*x, i);
+            assert_eq!((x as *const u32 as usize) % mem::align_of::<u32>(), 0);
+        }
+    }
+}
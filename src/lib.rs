@@ -3,9 +3,13 @@
 //! the `TypedArena` used in rustc. The main difference between this crate and
 //! the `typed_arena` crate is that this crate also provides an allocator which
 //! uses a `Mutex` internally instead of a `RefCell`; thus, the `sync::Arena`
-//! type is thread-safe.
+//! type is thread-safe. A third allocator, [`dropless::DroplessArena`], gives
+//! up per-element `Drop` in exchange for being able to allocate many
+//! different `Copy` types out of a single, untyped arena.
 
 
+use std::mem::MaybeUninit;
+
 // The initial size, in bytes, of a newly minted arena without a specified
 // capacity.
 const INITIAL_SIZE: usize = 1024;
@@ -16,13 +20,32 @@ const MIN_CAPACITY: usize = 1;
 
 mod chunk_list;
 
+pub mod dropless;
 pub mod sync;
 pub mod unsync;
 
 
+/// Assert that every element of `slice` has been initialized, and
+/// reinterpret it as `&mut [T]`.
+///
+/// Pairs with `unsync::Arena::alloc_uninitialized` /
+/// `sync::Arena::alloc_uninitialized`, which hand back contiguous,
+/// uninitialized storage for the caller to fill in place.
+///
+/// # Safety
+///
+/// Every element of `slice` must actually have been initialized, or reading
+/// the resulting `&mut [T]` is undefined behavior.
+pub unsafe fn assume_init_slice<T>(slice: &mut [MaybeUninit<T>]) -> &mut [T] {
+    &mut *(slice as *mut [MaybeUninit<T>] as *mut [T])
+}
+
+
 #[cfg(test)]
 mod test {
     use std::cell::Cell;
+    use std::mem;
+    use std::mem::MaybeUninit;
     use super::*;
 
     fn assert_send_and_sync<T: Send + Sync>() {}
@@ -95,11 +118,110 @@ mod test {
     }
 
 
+    struct SharedNode<'a>(Option<&'a SharedNode<'a>>, u32);
+
+
+    #[test]
+    fn unsync_alloc_shared_nested() {
+        let arena = unsync::Arena::new();
+
+        let outer = arena.alloc_shared(SharedNode(Some(arena.alloc_shared(SharedNode(None, 1))),
+                                                   2));
+
+        assert_eq!(outer.1, 2);
+        assert_eq!(outer.0.unwrap().1, 1);
+        assert!(outer.0.unwrap().0.is_none());
+    }
+
+
+    #[test]
+    fn unsync_alloc_extend_shared_nested() {
+        let arena = unsync::Arena::new();
+
+        let inner = arena.alloc_extend_shared(vec![1u32, 2]);
+        let outer = arena.alloc_extend_shared(inner.iter().cloned().chain(vec![3, 4]));
+
+        assert_eq!(inner, &[1, 2]);
+        assert_eq!(outer, &[1, 2, 3, 4]);
+    }
+
+
+    #[test]
+    fn unsync_alloc_uninitialized_larger_than_chunk_stays_contiguous() {
+        let arena = unsync::Arena::with_capacity(2);
+
+        let slots = arena.alloc_uninitialized(5);
+        for (i, slot) in slots.iter_mut().enumerate() {
+            *slot = MaybeUninit::new((i as u32 + 1) * 10);
+        }
+        let filled = unsafe { assume_init_slice(slots) };
+
+        assert_eq!(*filled, [10u32, 20, 30, 40, 50]);
+        for pair in filled.windows(2) {
+            let a = &pair[0] as *const u32 as usize;
+            let b = &pair[1] as *const u32 as usize;
+            assert_eq!(b - a, mem::size_of::<u32>());
+        }
+
+        let filled_start = filled.as_ptr() as usize;
+        let filled_end = filled_start + filled.len() * mem::size_of::<u32>();
+
+        let next = arena.alloc(999u32);
+        let next_addr = next as *const u32 as usize;
+        assert!(next_addr < filled_start || next_addr >= filled_end);
+    }
+
+
+    #[test]
+    fn unsync_into_vec_preserves_order_and_drops_once() {
+        let drop_counter = Cell::new(0);
+
+        let vec = {
+            let arena = unsync::Arena::with_capacity(2);
+
+            assert!(arena.is_empty());
+            assert_eq!(arena.len(), 0);
+
+            for i in 1..=5u32 {
+                arena.alloc(Node(None, i, DropTracker(&drop_counter)));
+            }
+
+            assert!(!arena.is_empty());
+            assert_eq!(arena.len(), 5);
+
+            arena.into_vec()
+        };
+
+        assert_eq!(drop_counter.get(), 0);
+        assert_eq!(vec.iter().map(|node| node.1).collect::<Vec<_>>(),
+                   vec![1, 2, 3, 4, 5]);
+
+        mem::drop(vec);
+        assert_eq!(drop_counter.get(), 5);
+    }
+
+
+    #[test]
+    fn unsync_iter_mut_visits_in_allocation_order_across_chunks() {
+        let mut arena = unsync::Arena::with_capacity(2);
+        for i in 0..10u32 {
+            arena.alloc(i);
+        }
+
+        for (i, x) in arena.iter_mut().enumerate() {
+            assert_eq!(*x, i as u32);
+            *x += 100;
+        }
+
+        assert_eq!(arena.into_vec(), (100..110).collect::<Vec<u32>>());
+    }
+
+
     #[test]
     fn sync_arena_as_intended() {
         let drop_counter = Cell::new(0);
         {
-            let arena = AtomicArena::with_capacity(2);
+            let arena = sync::Arena::with_capacity(2);
 
             let mut node: &Node = arena.alloc(Node(None, 1, DropTracker(&drop_counter)));
             assert_eq!(arena.chunks.lock().unwrap().rest.len(), 0);
@@ -142,4 +264,100 @@ mod test {
         }
         assert_eq!(drop_counter.get(), 7);
     }
+
+
+    #[test]
+    fn sync_alloc_shared_nested() {
+        let arena = sync::Arena::new();
+
+        let outer = arena.alloc_shared(SharedNode(Some(arena.alloc_shared(SharedNode(None, 1))),
+                                                   2));
+
+        assert_eq!(outer.1, 2);
+        assert_eq!(outer.0.unwrap().1, 1);
+        assert!(outer.0.unwrap().0.is_none());
+    }
+
+
+    #[test]
+    fn sync_alloc_extend_shared_nested() {
+        let arena = sync::Arena::new();
+
+        let inner = arena.alloc_extend_shared(vec![1u32, 2]);
+        let outer = arena.alloc_extend_shared(inner.iter().cloned().chain(vec![3, 4]));
+
+        assert_eq!(inner, &[1, 2]);
+        assert_eq!(outer, &[1, 2, 3, 4]);
+    }
+
+
+    #[test]
+    fn sync_alloc_uninitialized_larger_than_chunk_stays_contiguous() {
+        let arena = sync::Arena::with_capacity(2);
+
+        let slots = arena.alloc_uninitialized(5);
+        for (i, slot) in slots.iter_mut().enumerate() {
+            *slot = MaybeUninit::new((i as u32 + 1) * 10);
+        }
+        let filled = unsafe { assume_init_slice(slots) };
+
+        assert_eq!(*filled, [10u32, 20, 30, 40, 50]);
+        for pair in filled.windows(2) {
+            let a = &pair[0] as *const u32 as usize;
+            let b = &pair[1] as *const u32 as usize;
+            assert_eq!(b - a, mem::size_of::<u32>());
+        }
+
+        let filled_start = filled.as_ptr() as usize;
+        let filled_end = filled_start + filled.len() * mem::size_of::<u32>();
+
+        let next = arena.alloc(999u32);
+        let next_addr = next as *const u32 as usize;
+        assert!(next_addr < filled_start || next_addr >= filled_end);
+    }
+
+
+    #[test]
+    fn sync_into_vec_preserves_order_and_drops_once() {
+        let drop_counter = Cell::new(0);
+
+        let vec = {
+            let arena = sync::Arena::with_capacity(2);
+
+            assert!(arena.is_empty());
+            assert_eq!(arena.len(), 0);
+
+            for i in 1..=5u32 {
+                arena.alloc(Node(None, i, DropTracker(&drop_counter)));
+            }
+
+            assert!(!arena.is_empty());
+            assert_eq!(arena.len(), 5);
+
+            arena.into_vec()
+        };
+
+        assert_eq!(drop_counter.get(), 0);
+        assert_eq!(vec.iter().map(|node| node.1).collect::<Vec<_>>(),
+                   vec![1, 2, 3, 4, 5]);
+
+        mem::drop(vec);
+        assert_eq!(drop_counter.get(), 5);
+    }
+
+
+    #[test]
+    fn sync_iter_mut_visits_in_allocation_order_across_chunks() {
+        let mut arena = sync::Arena::with_capacity(2);
+        for i in 0..10u32 {
+            arena.alloc(i);
+        }
+
+        for (i, x) in arena.iter_mut().enumerate() {
+            assert_eq!(*x, i as u32);
+            *x += 100;
+        }
+
+        assert_eq!(arena.into_vec(), (100..110).collect::<Vec<u32>>());
+    }
 }
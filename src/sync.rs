@@ -1,5 +1,6 @@
-use std::iter;
 use std::mem;
+use std::mem::MaybeUninit;
+use std::slice;
 use std::sync::Mutex;
 
 use chunk_list::ChunkList;
@@ -7,7 +8,7 @@ use chunk_list::ChunkList;
 
 /// A `Sync` arena.
 pub struct Arena<T> {
-    chunks: Mutex<ChunkList<T>>,
+    pub(crate) chunks: Mutex<ChunkList<T>>,
 }
 
 
@@ -25,13 +26,7 @@ impl<T> Arena<T> {
 
     /// Allocate a single object in the arena.
     pub fn alloc(&self, t: T) -> &mut T {
-        unsafe {
-            mem::transmute::<&mut T, &mut T>(&mut self.chunks
-                                                      .lock()
-                                                      .unwrap()
-                                                      .alloc_extend(iter::once(t))
-                                                      [0])
-        }
+        unsafe { mem::transmute::<&mut T, &mut T>(self.chunks.lock().unwrap().alloc(t)) }
     }
 
     /// Allocate an arbitrary number of objects in the arena.
@@ -40,6 +35,77 @@ impl<T> Arena<T> {
             mem::transmute::<&mut [T], &mut [T]>(self.chunks.lock().unwrap().alloc_extend(iterable))
         }
     }
+
+    /// Allocate a single object in the arena, returning a shared reference.
+    ///
+    /// Unlike `alloc`, this is sound to nest - the classic recursive
+    /// pattern `arena.alloc_shared(Outer { inner: arena.alloc_shared(Inner
+    /// { .. }) })`, where both returned references stay live at once. The
+    /// returned reference is shared rather than exclusive,
+    /// so it doesn't claim sole access to the arena's storage the way
+    /// `alloc`'s `&mut T` does, and the `Mutex` guard is released before the
+    /// reference is formed rather than being laundered through it.
+    pub fn alloc_shared(&self, t: T) -> &T {
+        let mut chunks = self.chunks.lock().unwrap();
+        let ptr: *mut T = chunks.alloc(t);
+        mem::drop(chunks);
+        unsafe { &*ptr }
+    }
+
+    /// Allocate an arbitrary number of objects in the arena, returning a
+    /// shared reference to the resulting slice.
+    ///
+    /// See `alloc_shared` for why nested calls are sound. The `Mutex` stays
+    /// locked while `iterable` is drained, so an iterator that itself
+    /// allocates from this arena deadlocks or panics.
+    pub fn alloc_extend_shared<I: IntoIterator<Item = T>>(&self, iterable: I) -> &[T] {
+        let mut chunks = self.chunks.lock().unwrap();
+        let allocated = chunks.alloc_extend(iterable);
+        let ptr = allocated.as_mut_ptr();
+        let len = allocated.len();
+        mem::drop(chunks);
+        unsafe { slice::from_raw_parts(ptr, len) }
+    }
+
+    /// Reserve `n` contiguous, uninitialized slots in the arena, to be
+    /// filled in place - e.g. by a loop whose later elements need to read
+    /// earlier ones, which `alloc_extend` can't express since it consumes
+    /// an iterator. The whole block is guaranteed to land in a single
+    /// chunk, so the returned slice never straddles a chunk boundary.
+    ///
+    /// Every element of the returned slice must be initialized (see
+    /// `assume_init_slice`) before the arena is next used or dropped.
+    pub fn alloc_uninitialized(&self, n: usize) -> &mut [MaybeUninit<T>] {
+        unsafe {
+            mem::transmute::<&mut [MaybeUninit<T>], &mut [MaybeUninit<T>]>(self.chunks
+                                                                                .lock()
+                                                                                .unwrap()
+                                                                                .alloc_uninitialized(n))
+        }
+    }
+
+    /// Iterate mutably over every object allocated in the arena so far, in
+    /// allocation order. Since this takes `&mut self`, it needs no locking -
+    /// there can be no other live references into the arena while it's
+    /// borrowed.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.chunks.get_mut().unwrap().iter_mut()
+    }
+
+    /// Consume the arena, returning every allocated `T` in allocation order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.chunks.into_inner().unwrap().into_vec()
+    }
+
+    /// The number of objects allocated in the arena so far.
+    pub fn len(&self) -> usize {
+        self.chunks.lock().unwrap().len()
+    }
+
+    /// Whether the arena has allocated any objects yet.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.lock().unwrap().is_empty()
+    }
 }
 
 
@@ -77,16 +143,16 @@ mod test {
             let arena = Arena::with_capacity(2);
 
             let mut node: &Node = arena.alloc(Node(None, 1, DropTracker(&drop_counter)));
-            assert_eq!(arena.chunks.lock().unwrap().rest().len(), 0);
+            assert_eq!(arena.chunks.lock().unwrap().rest.len(), 0);
 
             node = arena.alloc(Node(Some(node), 2, DropTracker(&drop_counter)));
-            assert_eq!(arena.chunks.lock().unwrap().rest().len(), 0);
+            assert_eq!(arena.chunks.lock().unwrap().rest.len(), 0);
 
             node = arena.alloc(Node(Some(node), 3, DropTracker(&drop_counter)));
-            assert_eq!(arena.chunks.lock().unwrap().rest().len(), 1);
+            assert_eq!(arena.chunks.lock().unwrap().rest.len(), 1);
 
             node = arena.alloc(Node(Some(node), 4, DropTracker(&drop_counter)));
-            assert_eq!(arena.chunks.lock().unwrap().rest().len(), 1);
+            assert_eq!(arena.chunks.lock().unwrap().rest.len(), 1);
 
             assert_eq!(node.1, 4);
             assert_eq!(node.0.unwrap().1, 3);
@@ -98,13 +164,13 @@ mod test {
             assert_eq!(drop_counter.get(), 0);
 
             let mut node: &Node = arena.alloc(Node(None, 5, DropTracker(&drop_counter)));
-            assert_eq!(arena.chunks.lock().unwrap().rest().len(), 1);
+            assert_eq!(arena.chunks.lock().unwrap().rest.len(), 1);
 
             node = arena.alloc(Node(Some(node), 6, DropTracker(&drop_counter)));
-            assert_eq!(arena.chunks.lock().unwrap().rest().len(), 1);
+            assert_eq!(arena.chunks.lock().unwrap().rest.len(), 1);
 
             node = arena.alloc(Node(Some(node), 7, DropTracker(&drop_counter)));
-            assert_eq!(arena.chunks.lock().unwrap().rest().len(), 2);
+            assert_eq!(arena.chunks.lock().unwrap().rest.len(), 2);
 
             assert_eq!(drop_counter.get(), 0);
 